@@ -3,15 +3,68 @@ use crate::eth_provider::starknet::kakarot_core::MAX_FELTS_IN_CALLDATA;
 use crate::{
     eth_provider::{
         error::{EthApiError, SignatureError, TransactionError},
+        provider::EthereumProvider,
         starknet::kakarot_core::{get_white_listed_eip_155_transaction_hashes, ETH_SEND_TRANSACTION, KAKAROT_ADDRESS},
         utils::split_u256,
     },
     tracing::builder::TRACING_BLOCK_GAS_LIMIT,
 };
 use alloy_rlp::Encodable;
-use reth_primitives::{Transaction, TransactionSigned};
+use reth_primitives::{eip4844::calc_blob_gasprice, Transaction, TransactionSigned, B256};
 use reth_rpc_types::Header;
 use starknet::core::types::Felt;
+use std::collections::HashSet;
+
+/// Runtime-configurable parameters for [`validate_transaction`].
+///
+/// Bundles the chain id, an optional override for the tracing block gas limit, and the mutable set
+/// of pre EIP-155 transaction hashes operators may authorize without recompiling. The authorized
+/// set mirrors Kakarot's on-chain `set_authorized_pre_eip155_tx` / `get_authorized_pre_eip155_tx`
+/// entrypoints, letting a running node whitelist known legacy deployment transactions.
+#[derive(Debug, Clone)]
+pub struct TransactionValidationConfig {
+    /// The chain id transactions must target.
+    pub chain_id: u64,
+    /// Optional override for the tracing block gas limit; falls back to [`TRACING_BLOCK_GAS_LIMIT`].
+    pub tracing_gas_limit_override: Option<u64>,
+    /// Pre EIP-155 transaction hashes authorized to bypass the EIP-155 chain-id requirement.
+    authorized_pre_eip155_tx: HashSet<B256>,
+}
+
+impl TransactionValidationConfig {
+    /// Creates a new configuration for the given chain id, seeding the authorized pre EIP-155
+    /// transaction set from the compiled-in whitelist.
+    #[must_use]
+    pub fn new(chain_id: u64) -> Self {
+        Self {
+            chain_id,
+            tracing_gas_limit_override: None,
+            authorized_pre_eip155_tx: get_white_listed_eip_155_transaction_hashes().iter().copied().collect(),
+        }
+    }
+
+    /// Returns the effective tracing block gas limit, applying the override when set.
+    #[must_use]
+    pub fn tracing_gas_limit(&self) -> u64 {
+        self.tracing_gas_limit_override.unwrap_or(TRACING_BLOCK_GAS_LIMIT)
+    }
+
+    /// Authorizes a pre EIP-155 transaction hash at runtime.
+    pub fn set_authorized_pre_eip155_tx(&mut self, hash: B256) {
+        self.authorized_pre_eip155_tx.insert(hash);
+    }
+
+    /// Revokes authorization for a pre EIP-155 transaction hash, returning whether it was present.
+    pub fn remove_authorized_pre_eip155_tx(&mut self, hash: &B256) -> bool {
+        self.authorized_pre_eip155_tx.remove(hash)
+    }
+
+    /// Returns whether a pre EIP-155 transaction hash is authorized.
+    #[must_use]
+    pub fn is_authorized_pre_eip155_tx(&self, hash: &B256) -> bool {
+        self.authorized_pre_eip155_tx.contains(hash)
+    }
+}
 
 /// Validates the signed ethereum transaction.
 /// The validation checks the following:
@@ -19,46 +72,60 @@ use starknet::core::types::Felt;
 /// - The transaction chain id (if any) is the same as the one provided.
 /// - The transaction hash is whitelisted for pre EIP-155 transactions.
 /// - The transaction signature can be recovered.
+/// - The transaction sender is an externally owned account carrying no code (EIP-3607).
 /// - The transaction base fee is lower than the max fee per gas.
 /// - The transaction max priority fee is lower than the max fee per gas.
+/// - For blob (EIP-4844) transactions, the blob set is non-empty and within the per-transaction
+///   limit, and the blob fee cap covers the parent block's blob base fee.
 /// - The transaction gas limit is lower than the block's gas limit.
 ///
 /// # Errors
 ///
 /// Returns an error if the transaction is invalid.
-pub(crate) fn validate_transaction(
+pub(crate) async fn validate_transaction<P: EthereumProvider>(
     transaction_signed: &TransactionSigned,
-    chain_id: u64,
+    config: &TransactionValidationConfig,
     previous_block_header: &Header,
+    eth_provider: &P,
 ) -> Result<(), EthApiError> {
     // If the transaction gas limit is higher than the tracing
     // block gas limit, prevent the transaction from being sent
     // (it will revert anyway on the Starknet side). This assures
     // that all transactions are traceable.
-    if transaction_signed.gas_limit() > TRACING_BLOCK_GAS_LIMIT {
+    if transaction_signed.gas_limit() > config.tracing_gas_limit() {
         return Err(TransactionError::GasOverflow.into());
     }
 
     // Recover the signer from the transaction
-    let _ = transaction_signed.recover_signer().ok_or(SignatureError::Recovery)?;
+    let signer = transaction_signed.recover_signer().ok_or(SignatureError::Recovery)?;
 
     // Assert the chain is correct
     let maybe_chain_id = transaction_signed.chain_id();
-    if !maybe_chain_id.map_or(true, |c| c == chain_id) {
+    if !maybe_chain_id.map_or(true, |c| c == config.chain_id) {
         return Err(TransactionError::InvalidChainId.into());
     }
 
-    // If the transaction is a pre EIP-155 transaction, check if hash is whitelisted
-    if maybe_chain_id.is_none() && !get_white_listed_eip_155_transaction_hashes().contains(&transaction_signed.hash) {
+    // If the transaction is a pre EIP-155 transaction, check if hash is authorized
+    if maybe_chain_id.is_none() && !config.is_authorized_pre_eip155_tx(&transaction_signed.hash) {
         return Err(TransactionError::InvalidTransactionType.into());
     }
 
-    let base_fee = previous_block_header.base_fee_per_gas.unwrap_or_default();
+    // EIP-3607: reject transactions sent from an account that has deployed bytecode.
+    // A contract account must never originate a transaction, so if the recovered sender
+    // carries code on the Kakarot side we refuse to relay it. This state read is kept after
+    // the cheap, local chain-id and whitelist checks so transactions rejected by those never pay for it.
+    if !eth_provider.get_code(signer, None).await?.is_empty() {
+        return Err(TransactionError::SenderNotEoa(signer).into());
+    }
+
+    // The transaction will be included in the *next* block, whose base fee differs from the
+    // parent's, so we validate against the projected base fee rather than `previous_block_header`'s.
+    let next_base_fee = next_block_base_fee(previous_block_header);
     let max_fee_per_gas = transaction_signed.max_fee_per_gas();
 
-    // Check if the base fee is lower than the max fee per gas
-    if base_fee > max_fee_per_gas {
-        return Err(TransactionError::FeeCapTooLow(max_fee_per_gas, base_fee).into());
+    // Check if the projected next-block base fee is lower than the max fee per gas
+    if next_base_fee > max_fee_per_gas {
+        return Err(TransactionError::FeeCapTooLow(max_fee_per_gas, next_base_fee).into());
     }
 
     let max_priority_fee_per_gas = transaction_signed.max_priority_fee_per_gas().unwrap_or_default();
@@ -68,6 +135,26 @@ pub(crate) fn validate_transaction(
         return Err(TransactionError::TipAboveFeeCap(max_fee_per_gas, max_priority_fee_per_gas).into());
     }
 
+    // EIP-4844: blob transactions carry an additional blob-gas fee market and a set of versioned
+    // hashes that must be present and within the per-transaction limit.
+    if let Transaction::Eip4844(blob_tx) = &transaction_signed.transaction {
+        let blob_count = blob_tx.blob_versioned_hashes.len();
+        if blob_count == 0 {
+            return Err(TransactionError::NoBlobs.into());
+        }
+        if blob_count > MAX_BLOBS_PER_TX {
+            return Err(TransactionError::TooManyBlobs(blob_count, MAX_BLOBS_PER_TX).into());
+        }
+
+        // The blob fee cap must cover the parent block's blob base fee, derived from its excess
+        // blob gas via the EIP-4844 exponential pricing function.
+        let blob_base_fee =
+            previous_block_header.excess_blob_gas.map(|excess| calc_blob_gasprice(excess as u64)).unwrap_or_default();
+        if blob_tx.max_fee_per_blob_gas < blob_base_fee {
+            return Err(TransactionError::BlobFeeCapTooLow(blob_tx.max_fee_per_blob_gas, blob_base_fee).into());
+        }
+    }
+
     let transaction_gas_limit = transaction_signed.gas_limit().into();
     let block_gas_limit = previous_block_header.gas_limit;
 
@@ -76,9 +163,94 @@ pub(crate) fn validate_transaction(
         return Err(TransactionError::ExceedsBlockGasLimit(transaction_gas_limit, block_gas_limit).into());
     }
 
+    // Check that the gas limit covers at least the transaction's intrinsic cost. Under-funded
+    // transactions would otherwise revert on the Starknet side after a wasted round-trip.
+    let intrinsic_gas = intrinsic_gas_cost(&transaction_signed.transaction);
+    if transaction_signed.gas_limit() < intrinsic_gas {
+        return Err(TransactionError::IntrinsicGasTooLow(transaction_signed.gas_limit(), intrinsic_gas).into());
+    }
+
     Ok(())
 }
 
+/// Maximum number of blob versioned hashes a single EIP-4844 transaction may carry.
+const MAX_BLOBS_PER_TX: usize = 6;
+
+/// Elasticity multiplier used to derive the gas target from the block gas limit (EIP-1559).
+const ELASTICITY_MULTIPLIER: u128 = 2;
+/// Maximum fraction by which the base fee can change between consecutive blocks (EIP-1559).
+const BASE_FEE_MAX_CHANGE_DENOMINATOR: u128 = 8;
+
+/// Projects the base fee of the block the transaction will be mined in by applying the EIP-1559
+/// base-fee recurrence to the parent header. The next base fee moves towards the parent's gas
+/// usage: it rises when the parent was above the gas target, falls when it was below, and is
+/// unchanged when usage matched the target exactly. The result never drops below zero.
+fn next_block_base_fee(parent: &Header) -> u128 {
+    let parent_base_fee = parent.base_fee_per_gas.unwrap_or_default();
+    let gas_target = parent.gas_limit / ELASTICITY_MULTIPLIER;
+
+    // A zero gas target (a parent gas limit below 2) leaves the base fee unchanged.
+    if gas_target == 0 || parent.gas_used == gas_target {
+        return parent_base_fee;
+    }
+
+    if parent.gas_used > gas_target {
+        let delta =
+            parent_base_fee * (parent.gas_used - gas_target) / gas_target / BASE_FEE_MAX_CHANGE_DENOMINATOR;
+        parent_base_fee + delta.max(1)
+    } else {
+        let delta =
+            parent_base_fee * (gas_target - parent.gas_used) / gas_target / BASE_FEE_MAX_CHANGE_DENOMINATOR;
+        parent_base_fee.saturating_sub(delta)
+    }
+}
+
+/// Per-transaction base cost.
+const TX_GAS: u64 = 21_000;
+/// Per-transaction cost for contract creation (replaces [`TX_GAS`] when `to` is `None`).
+const TX_CREATE_GAS: u64 = 53_000;
+/// Cost of a zero byte of calldata.
+const TX_DATA_ZERO_GAS: u64 = 4;
+/// Cost of a non-zero byte of calldata.
+const TX_DATA_NON_ZERO_GAS: u64 = 16;
+/// Cost per address in an EIP-2930 access list.
+const ACCESS_LIST_ADDRESS_GAS: u64 = 2_400;
+/// Cost per storage key in an EIP-2930 access list.
+const ACCESS_LIST_STORAGE_KEY_GAS: u64 = 1_900;
+/// EIP-3860 cost per 32-byte word of init code on contract creation.
+const INIT_CODE_WORD_GAS: u64 = 2;
+
+/// Computes the minimum intrinsic gas a transaction must pay before execution, following the
+/// Ethereum yellow paper together with EIP-2028 (calldata), EIP-2930 (access lists) and EIP-3860
+/// (init-code metering).
+fn intrinsic_gas_cost(transaction: &Transaction) -> u64 {
+    let is_create = transaction.to().is_none();
+
+    let mut gas = if is_create { TX_CREATE_GAS } else { TX_GAS };
+
+    // Calldata cost: 4 gas per zero byte, 16 gas per non-zero byte.
+    let input = transaction.input();
+    let zero_bytes = input.iter().filter(|&&byte| byte == 0).count() as u64;
+    let non_zero_bytes = input.len() as u64 - zero_bytes;
+    gas += zero_bytes * TX_DATA_ZERO_GAS + non_zero_bytes * TX_DATA_NON_ZERO_GAS;
+
+    // Access-list cost for EIP-2930 and EIP-1559 transactions.
+    if let Some(access_list) = transaction.access_list() {
+        for item in access_list.iter() {
+            gas += ACCESS_LIST_ADDRESS_GAS;
+            gas += item.storage_keys.len() as u64 * ACCESS_LIST_STORAGE_KEY_GAS;
+        }
+    }
+
+    // EIP-3860 init-code cost: 2 gas per 32-byte word, rounded up, on contract creation.
+    if is_create {
+        let words = (input.len() as u64).div_ceil(32);
+        gas += words * INIT_CODE_WORD_GAS;
+    }
+
+    gas
+}
+
 /// Returns the transaction's signature as a [`Vec<Felt>`].
 /// Fields r and s are split into two 16-bytes chunks both converted
 /// to [`Felt`].
@@ -110,6 +282,11 @@ pub(crate) fn transaction_signature_to_field_elements(transaction_signed: &Trans
 ///
 /// For Legacy Transactions: rlp([nonce, `gas_price`, `gas_limit`, to, value, data, `chain_id`, 0, 0])
 /// is then converted to a [`Vec<Felt>`], packing the data in 31-byte chunks.
+///
+/// Typed transactions are RLP-encoded according to their envelope via `encode_without_signature`.
+/// For EIP-4844 blob transactions the envelope already carries the `max_fee_per_blob_gas` and
+/// `blob_versioned_hashes` fields, so no dedicated branch is needed: the generic path packs the
+/// versioned hashes into the 31-byte chunks the Kakarot EOA receives (see the unit test below).
 #[allow(clippy::unnecessary_wraps)]
 pub(crate) fn transaction_data_to_starknet_calldata(
     transaction_signed: &TransactionSigned,
@@ -155,3 +332,127 @@ pub(crate) fn transaction_data_to_starknet_calldata(
 
     Ok(calldata)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_primitives::{
+        AccessList, AccessListItem, Address, Bytes, Signature, TxEip1559, TxKind, TxLegacy, TxEip4844, U256,
+    };
+
+    /// Reconstructs the raw RLP byte buffer from the packed `signed_data` felts produced by
+    /// [`transaction_data_to_starknet_calldata`]: the first felt is the byte length, the rest are
+    /// big-endian 31-byte chunks.
+    fn unpack_signed_data(signed_data: &[Felt]) -> Vec<u8> {
+        let total_len = signed_data[0].to_string().parse::<usize>().unwrap();
+        let mut bytes = Vec::with_capacity(total_len);
+        for chunk in &signed_data[1..] {
+            let remaining = total_len - bytes.len();
+            let chunk_len = remaining.min(31);
+            let felt_bytes = chunk.to_bytes_be();
+            bytes.extend_from_slice(&felt_bytes[felt_bytes.len() - chunk_len..]);
+        }
+        bytes
+    }
+
+    #[test]
+    fn test_blob_versioned_hashes_are_packed_into_calldata() {
+        let versioned_hash = B256::repeat_byte(0xab);
+        let transaction = Transaction::Eip4844(TxEip4844 {
+            blob_versioned_hashes: vec![versioned_hash],
+            ..Default::default()
+        });
+        let signed = TransactionSigned::from_transaction_and_signature(
+            transaction,
+            Signature { r: U256::ZERO, s: U256::ZERO, odd_y_parity: false },
+        );
+
+        // The first 6 felts are the Starknet invoke header; the packed tx data follows.
+        let calldata = transaction_data_to_starknet_calldata(&signed, 0).unwrap();
+        let raw = unpack_signed_data(&calldata[6..]);
+
+        assert!(
+            raw.windows(32).any(|window| window == versioned_hash.as_slice()),
+            "the blob versioned hash must survive the 31-byte-chunk packing"
+        );
+    }
+
+    #[test]
+    fn test_intrinsic_gas_cost() {
+        // (description, transaction, expected intrinsic gas)
+        let cases = [
+            (
+                "plain call",
+                Transaction::Legacy(TxLegacy { to: TxKind::Call(Address::ZERO), ..Default::default() }),
+                21_000,
+            ),
+            (
+                "empty contract creation",
+                Transaction::Legacy(TxLegacy { to: TxKind::Create, ..Default::default() }),
+                53_000,
+            ),
+            (
+                "call with mixed calldata: 2 zero + 3 non-zero bytes",
+                Transaction::Legacy(TxLegacy {
+                    to: TxKind::Call(Address::ZERO),
+                    input: Bytes::from(vec![0, 1, 0, 2, 3]),
+                    ..Default::default()
+                }),
+                21_000 + 2 * 4 + 3 * 16,
+            ),
+            (
+                "1559 call with an access list (1 address, 2 storage keys)",
+                Transaction::Eip1559(TxEip1559 {
+                    to: TxKind::Call(Address::ZERO),
+                    access_list: AccessList(vec![AccessListItem {
+                        address: Address::ZERO,
+                        storage_keys: vec![B256::ZERO, B256::repeat_byte(1)],
+                    }]),
+                    ..Default::default()
+                }),
+                21_000 + 2_400 + 2 * 1_900,
+            ),
+            (
+                "creation with 33-byte init code rounds up to 2 words",
+                Transaction::Legacy(TxLegacy {
+                    to: TxKind::Create,
+                    input: Bytes::from(vec![1u8; 33]),
+                    ..Default::default()
+                }),
+                53_000 + 33 * 16 + 2 * 2,
+            ),
+        ];
+
+        for (description, transaction, expected) in cases {
+            assert_eq!(intrinsic_gas_cost(&transaction), expected, "{description}");
+        }
+    }
+
+    /// Builds a parent header with the given base fee and gas usage, defaulting the rest.
+    fn parent_header(base_fee: u128, gas_limit: u128, gas_used: u128) -> Header {
+        Header { base_fee_per_gas: Some(base_fee), gas_limit, gas_used, ..Default::default() }
+    }
+
+    #[test]
+    fn test_next_block_base_fee() {
+        // (description, parent header, expected next-block base fee)
+        let cases = [
+            // gas_used == gas_target: base fee unchanged.
+            ("usage at target", parent_header(1_000, 100, 50), 1_000),
+            // gas_used > gas_target: base fee rises by parent * delta / target / 8.
+            ("usage above target", parent_header(1_000, 100, 75), 1_000 + 62),
+            // gas_used < gas_target: base fee falls by the same formula.
+            ("usage below target", parent_header(1_000, 100, 25), 1_000 - 62),
+            // Above target but the computed delta rounds to zero: the increase is bumped to 1.
+            ("above target with min bump", parent_header(1, 100, 51), 2),
+            // Zero parent gas limit (gas_target == 0): base fee unchanged.
+            ("zero gas limit parent", parent_header(1_000, 0, 0), 1_000),
+            // Below target can never underflow below zero: the decrease rounds down to 0 here.
+            ("below target cannot underflow", parent_header(1, 100, 0), 1),
+        ];
+
+        for (description, header, expected) in cases {
+            assert_eq!(next_block_base_fee(&header), expected, "{description}");
+        }
+    }
+}